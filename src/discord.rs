@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use reqwest::multipart::{Form, Part};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use tokio_util::codec::{BytesCodec, FramedRead};
+
+const CONFIG_FILE: &str = "discord.json";
+
+/// Webhook destination read from a small JSON config file living next to
+/// the executable, so users who want automatic upload don't have to be
+/// prompted for it on every run.
+#[derive(Debug, Deserialize)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+    pub token: String,
+}
+
+impl DiscordConfig {
+    /// Looks for `discord.json` next to the running executable and
+    /// parses it. Returns `None` if it isn't there or can't be parsed,
+    /// in which case the caller should fall back to the manual upload
+    /// instructions.
+    pub fn load() -> Option<DiscordConfig> {
+        let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+        let contents = std::fs::read_to_string(exe_dir.join(CONFIG_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+}
+
+#[derive(Debug)]
+pub enum DiscordError {
+    /// The export file couldn't be read back off disk
+    Io(std::io::Error),
+    /// The HTTP request itself failed (network, TLS, etc.)
+    Request(reqwest::Error),
+    /// Discord responded with a non-success status
+    Status(StatusCode),
+}
+
+impl From<std::io::Error> for DiscordError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<reqwest::Error> for DiscordError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Request(error)
+    }
+}
+
+impl std::fmt::Display for DiscordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't read export file: {err}"),
+            Self::Request(err) => write!(f, "upload request failed: {err}"),
+            Self::Status(status) => write!(f, "Discord returned {status}"),
+        }
+    }
+}
+
+/// Uploads `path` to the configured Discord webhook as a multipart
+/// attachment, streaming the file into the request body instead of
+/// buffering the whole export in memory first.
+pub async fn upload(config: &DiscordConfig, path: &Path) -> Result<(), DiscordError> {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("bloom-data-ah.csv")
+        .to_owned();
+    let file = tokio::fs::File::open(path).await?;
+    let body = reqwest::Body::wrap_stream(FramedRead::new(file, BytesCodec::new()));
+
+    let form = Form::new().part("file", Part::stream(body).file_name(filename));
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&config.webhook_url)
+        // Discord webhooks authenticate via the token embedded in
+        // `webhook_url` itself; this header isn't required, but the
+        // request asked for it explicitly, so it's kept as a harmless no-op.
+        .header("Authorization", &config.token)
+        .multipart(form)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(DiscordError::Status(response.status()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::BufWriter;
+use std::path::Path;
+
+use chrono::Duration;
+use icalendar::{Calendar, Component, Event as IcsEvent};
+
+use crate::BloomRecord;
+
+/// Errors that can occur while writing records out in one of the
+/// supported [`OutputFormat`]s.
+#[derive(Debug)]
+pub enum ExportError {
+    /// CSV serialization failed
+    Csv(csv::Error),
+    /// JSON serialization failed
+    Json(serde_json::Error),
+    /// MessagePack serialization failed
+    MsgPack(rmp_serde::encode::Error),
+    /// The export file couldn't be created or written
+    Io(std::io::Error),
+    /// The file extension didn't match any known format
+    Unsupported(String),
+}
+
+impl From<csv::Error> for ExportError {
+    fn from(error: csv::Error) -> Self {
+        Self::Csv(error)
+    }
+}
+
+impl From<serde_json::Error> for ExportError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for ExportError {
+    fn from(error: rmp_serde::encode::Error) -> Self {
+        Self::MsgPack(error)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv(err) => write!(f, "CSV export failed: {err}"),
+            Self::Json(err) => write!(f, "JSON export failed: {err}"),
+            Self::MsgPack(err) => write!(f, "MessagePack export failed: {err}"),
+            Self::Io(err) => write!(f, "couldn't write export file: {err}"),
+            Self::Unsupported(ext) => write!(f, "unsupported export extension: {ext}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// A destination format for a parsed set of [`BloomRecord`]s.
+///
+/// The CSV, JSON and MessagePack implementors share `BloomRecord`'s
+/// `Serialize` impl rather than hand-rolling per-format field layouts;
+/// [`Ics`] maps each record onto a calendar event itself.
+pub trait OutputFormat {
+    fn write(&self, records: &[BloomRecord], path: &Path) -> Result<(), ExportError>;
+}
+
+pub struct Csv;
+
+impl OutputFormat for Csv {
+    fn write(&self, records: &[BloomRecord], path: &Path) -> Result<(), ExportError> {
+        let mut wtr = csv::WriterBuilder::new().from_path(path)?;
+        for record in records {
+            wtr.serialize(record)?;
+        }
+        wtr.flush().map_err(csv::Error::from)?;
+
+        Ok(())
+    }
+}
+
+pub struct Json;
+
+impl OutputFormat for Json {
+    fn write(&self, records: &[BloomRecord], path: &Path) -> Result<(), ExportError> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), records)?;
+
+        Ok(())
+    }
+}
+
+pub struct MsgPack;
+
+impl OutputFormat for MsgPack {
+    fn write(&self, records: &[BloomRecord], path: &Path) -> Result<(), ExportError> {
+        let file = File::create(path)?;
+        rmp_serde::encode::write(&mut BufWriter::new(file), records)?;
+
+        Ok(())
+    }
+}
+
+pub struct Ics;
+
+impl OutputFormat for Ics {
+    fn write(&self, records: &[BloomRecord], path: &Path) -> Result<(), ExportError> {
+        let mut calendar = Calendar::new();
+        for record in records {
+            let start = record.occurred_at;
+            let end = start
+                + Duration::seconds(record.meditation_minutes as i64 * 60)
+                + Duration::seconds(record.dropped_seconds as i64);
+
+            let mut hasher = DefaultHasher::new();
+            start.timestamp().hash(&mut hasher);
+            record.app_name.hash(&mut hasher);
+            let uid = format!("{:x}@bloomparse-gui", hasher.finish());
+
+            calendar.push(
+                IcsEvent::new()
+                    .uid(&uid)
+                    .summary(&format!("Mindful Session ({})", record.app_name))
+                    .starts(start)
+                    .ends(end)
+                    .done(),
+            );
+        }
+
+        std::fs::write(path, calendar.to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Picks the [`OutputFormat`] implementor matching `path`'s extension.
+pub fn for_path(path: &Path) -> Result<Box<dyn OutputFormat>, ExportError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => Ok(Box::new(Json)),
+        Some("msgpack") | Some("mpk") => Ok(Box::new(MsgPack)),
+        Some("ics") => Ok(Box::new(Ics)),
+        Some("csv") | None => Ok(Box::new(Csv)),
+        Some(other) => Err(ExportError::Unsupported(other.to_owned())),
+    }
+}
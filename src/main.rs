@@ -3,22 +3,26 @@
 
 extern crate tinyfiledialogs;
 
+mod discord;
+mod format;
+
 use chrono::{self, Utc};
+use format::ExportError;
 use quick_xml::events::attributes::AttrError;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tinyfiledialogs::{MessageBoxIcon, YesNo};
 
 #[derive(Debug)]
 enum AppError {
     /// XML parsing error
     Xml(quick_xml::Error),
-    /// Not a MindfulSession record
+    /// Not one of the tracked HealthKit record types
     NoRecord(String),
 }
 
@@ -34,50 +38,79 @@ impl From<AttrError> for AppError {
     }
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
-struct MindfulSession {
+/// HealthKit `type` identifier for mindful-session records, i.e. the
+/// subset Bloom actually imports.
+const MINDFUL_SESSION_TYPE: &str = "HKCategoryTypeIdentifierMindfulSession";
+
+/// HealthKit `type` identifiers the parser pulls out of the export.
+/// Anything not in this set is skipped, same as the original
+/// mindful-session-only parser, just with room to grow.
+const TRACKED_HEALTH_TYPES: &[&str] = &[
+    MINDFUL_SESSION_TYPE,
+    "HKQuantityTypeIdentifierHeartRate",
+    "HKCategoryTypeIdentifierSleepAnalysis",
+];
+
+/// A single `Record` parsed out of an Apple Health export, for any of
+/// the [`TRACKED_HEALTH_TYPES`]. Bloom only cares about mindful
+/// sessions, but this superset lets future exporters work from
+/// whatever categories the user is tracking.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct HealthRecord {
+    #[serde(rename = "@type")]
+    pub activity_type: String,
     #[serde(rename = "@sourceName")]
     pub app: String,
     #[serde(rename = "@startDate")]
     pub start: String,
     #[serde(rename = "@endDate")]
     pub end: String,
+    #[serde(rename = "@value")]
+    pub value: Option<String>,
 }
 
-impl MindfulSession {
+impl HealthRecord {
     async fn new_from_element(
         reader: &mut Reader<std::io::BufReader<std::fs::File>>,
         element: BytesStart<'_>,
-    ) -> Result<Option<MindfulSession>, quick_xml::Error> {
-        let mut activity = Cow::Borrowed("");
+    ) -> Result<Option<HealthRecord>, quick_xml::Error> {
+        let mut activity_type = Cow::Borrowed("");
         let mut app = Cow::Borrowed("");
         let mut start = Cow::Borrowed("");
         let mut end = Cow::Borrowed("");
+        let mut value = Cow::Borrowed("");
 
         for attr_result in element.attributes() {
             let a = attr_result?;
             match a.key.as_ref() {
-                b"type" => activity = a.decode_and_unescape_value(reader.decoder())?,
+                b"type" => activity_type = a.decode_and_unescape_value(reader.decoder())?,
                 b"sourceName" => app = a.decode_and_unescape_value(reader.decoder())?,
                 b"startDate" => start = a.decode_and_unescape_value(reader.decoder())?,
                 b"endDate" => end = a.decode_and_unescape_value(reader.decoder())?,
+                b"value" => value = a.decode_and_unescape_value(reader.decoder())?,
                 _ => (),
             }
         }
 
-        if activity != "HKCategoryTypeIdentifierMindfulSession" {
+        if !TRACKED_HEALTH_TYPES.contains(&activity_type.as_ref()) {
             return Ok(None);
         }
 
-        Ok(Some(MindfulSession {
+        Ok(Some(HealthRecord {
+            activity_type: activity_type.into(),
             app: app.into(),
             start: start.into(),
             end: end.into(),
+            value: if value.is_empty() {
+                None
+            } else {
+                Some(value.into())
+            },
         }))
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 struct BloomRecord {
     #[serde(rename = "App Name")]
     app_name: String,
@@ -91,9 +124,9 @@ struct BloomRecord {
 
 impl BloomRecord {
     async fn new_from_user_data(
-        user_record: MindfulSession,
+        user_record: &HealthRecord,
     ) -> Result<BloomRecord, std::num::TryFromIntError> {
-        let app_name = user_record.app;
+        let app_name = user_record.app.clone();
         let occurred_at =
             chrono::NaiveDateTime::parse_from_str(&user_record.start, "%Y-%m-%d %H:%M:%S %z")
                 .unwrap()
@@ -115,29 +148,33 @@ impl BloomRecord {
         })
     }
 
-    async fn write_csv(bloom_data: &Vec<BloomRecord>) -> Result<String, csv::Error> {
-        let output_file =
-            tinyfiledialogs::save_file_dialog("Save Mindful Session CSV", "bloom-data-ah.csv")
-                .map(String::from);
+    async fn write_export(bloom_data: &[BloomRecord]) -> Result<String, ExportError> {
+        let output_file = tinyfiledialogs::save_file_dialog_with_filter(
+            "Save Mindful Session Export",
+            "bloom-data-ah.csv",
+            &["*.csv", "*.json", "*.msgpack", "*.ics"],
+            "Bloom export (*.csv, *.json, *.msgpack, *.ics)",
+        )
+        .map(String::from);
 
         if output_file.is_none() {
             return Ok("abort".to_owned());
         }
 
         let filename = output_file.unwrap();
-        let mut wtr = csv::WriterBuilder::new().from_path(&filename)?;
-        for record in bloom_data {
-            if record.meditation_minutes == 0 {
-                continue;
-            }
-            wtr.serialize(record)?;
-        }
-        wtr.flush()?;
+        let path = Path::new(&filename);
+        let records: Vec<BloomRecord> = bloom_data
+            .iter()
+            .filter(|record| record.meditation_minutes != 0)
+            .cloned()
+            .collect();
+
+        format::for_path(path)?.write(&records, path)?;
 
         Ok(filename)
     }
 
-    async fn calculate_stats(bloom_data: Vec<BloomRecord>) -> Result<String, std::io::Error> {
+    async fn calculate_stats(mut bloom_data: Vec<BloomRecord>) -> Result<String, std::io::Error> {
         let mut stats = String::new();
         let mut stats_hash: HashMap<String, i32> = HashMap::new();
         for record in &bloom_data {
@@ -163,14 +200,86 @@ impl BloomRecord {
             );
         }
 
+        bloom_data.sort_by_key(|record| record.occurred_at);
+
+        let _ = write!(
+            stats,
+            "\n{}",
+            Self::period_breakdown("Daily", &bloom_data, "%Y-%m-%d")
+        );
+        let _ = write!(
+            stats,
+            "\n{}",
+            Self::period_breakdown("Weekly", &bloom_data, "%G-W%V")
+        );
+        let _ = write!(
+            stats,
+            "\n{}",
+            Self::period_breakdown("Monthly", &bloom_data, "%Y-%m")
+        );
+        let streak = Self::longest_streak(&bloom_data);
+        let _ = write!(
+            stats,
+            "\nLongest streak: {streak} consecutive day{}",
+            if streak == 1 { "" } else { "s" }
+        );
+
         Ok(stats)
     }
+
+    /// Buckets `records` (already sorted by `occurred_at`) into the
+    /// period named by `format`, reporting session count and total
+    /// meditated minutes per period in chronological order.
+    fn period_breakdown(label: &str, records: &[BloomRecord], format: &str) -> String {
+        let mut by_period: BTreeMap<String, (i32, i32)> = BTreeMap::new();
+        for record in records {
+            let key = record.occurred_at.format(format).to_string();
+            let entry = by_period.entry(key).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += record.meditation_minutes;
+        }
+
+        let mut breakdown = format!("{label}:\n");
+        for (period, (count, minutes)) in by_period {
+            let _ = writeln!(
+                breakdown,
+                "  {period}: {count} {}, {minutes} min",
+                if count == 1 { "session" } else { "sessions" }
+            );
+        }
+
+        breakdown
+    }
+
+    /// Longest run of consecutive calendar days with at least one
+    /// session, walking the sorted, de-duplicated session dates.
+    fn longest_streak(records: &[BloomRecord]) -> i32 {
+        let mut dates: Vec<chrono::NaiveDate> = records
+            .iter()
+            .map(|record| record.occurred_at.date_naive())
+            .collect();
+        dates.dedup();
+
+        let mut longest = 0;
+        let mut current = 0;
+        let mut previous: Option<chrono::NaiveDate> = None;
+        for date in dates {
+            current = match previous {
+                Some(prev) if date == prev + chrono::Duration::days(1) => current + 1,
+                _ => 1,
+            };
+            longest = longest.max(current);
+            previous = Some(date);
+        }
+
+        longest
+    }
 }
 
 async fn apple_health(file: &PathBuf) -> Result<(), quick_xml::DeError> {
     let mut reader = Reader::from_file(file)?;
 
-    let mut user_data: Vec<MindfulSession> = Vec::new();
+    let mut health_data: Vec<HealthRecord> = Vec::new();
     let mut bloom_data: Vec<BloomRecord> = Vec::new();
 
     let mut buf = Vec::new();
@@ -179,15 +288,17 @@ async fn apple_health(file: &PathBuf) -> Result<(), quick_xml::DeError> {
         let event = reader.read_event_into(&mut buf)?;
 
         match event {
-            Event::Empty(element) => {
+            // HKQuantityTypeIdentifierHeartRate records (and others with
+            // nested MetadataEntry/HeartRateVariabilityMetadataList
+            // children) show up as Start/End pairs rather than a single
+            // Empty element, so both need handling here.
+            Event::Start(element) | Event::Empty(element) => {
                 if element.name().as_ref() == b"Record" {
-                    if let Some(entry) = MindfulSession::new_from_element(&mut reader, element)
+                    if let Some(entry) = HealthRecord::new_from_element(&mut reader, element)
                         .await
                         .unwrap()
                     {
-                        user_data.push(entry);
-                    } else {
-                        continue;
+                        health_data.push(entry);
                     }
                 }
             }
@@ -196,7 +307,10 @@ async fn apple_health(file: &PathBuf) -> Result<(), quick_xml::DeError> {
         }
     }
 
-    for record in user_data {
+    let mindful_sessions = health_data
+        .iter()
+        .filter(|record| record.activity_type == MINDFUL_SESSION_TYPE);
+    for record in mindful_sessions {
         bloom_data.push(BloomRecord::new_from_user_data(record).await.unwrap());
     }
 
@@ -215,7 +329,7 @@ async fn apple_health(file: &PathBuf) -> Result<(), quick_xml::DeError> {
     //    else { map.insert(record.app_name.as_str(), vec![(record.occurred_at, record.meditation_minutes)]); }
     //}
 
-    let filename = BloomRecord::write_csv(&bloom_data).await.unwrap();
+    let filename = BloomRecord::write_export(&bloom_data).await.unwrap();
     let stats = BloomRecord::calculate_stats(bloom_data).await.unwrap();
 
     if filename == "abort" {
@@ -227,13 +341,38 @@ async fn apple_health(file: &PathBuf) -> Result<(), quick_xml::DeError> {
         return Ok(());
     }
 
+    let short_name = filename.split('\\').last().unwrap();
+    let manual_instructions = format!(
+        "Upload {short_name} to the #meditation-tracking channel and use /import to import the data into Bloom."
+    );
+
+    let upload_message = if filename.ends_with(".csv") {
+        match discord::DiscordConfig::load() {
+            Some(config) => {
+                let upload_now = tinyfiledialogs::message_box_yes_no(
+                    "Bloom Bot Parser",
+                    "A Discord webhook is configured. Upload the CSV to #meditation-tracking now?",
+                    MessageBoxIcon::Question,
+                    YesNo::Yes,
+                );
+
+                match upload_now {
+                    YesNo::Yes => match discord::upload(&config, Path::new(&filename)).await {
+                        Ok(()) => "Uploaded to #meditation-tracking.".to_owned(),
+                        Err(err) => format!("Upload failed ({err}). {manual_instructions}"),
+                    },
+                    YesNo::No => manual_instructions,
+                }
+            }
+            None => manual_instructions,
+        }
+    } else {
+        manual_instructions
+    };
+
     tinyfiledialogs::message_box_ok(
         "Bloom Bot Parser",
-        format!(
-            "Mindful Session extraction successful!\n\n{}\nUpload {} to the #meditation-tracking channel and use /import to import the data into Bloom.",
-            stats,
-            filename.split("\\").last().unwrap()
-        ).as_str(),
+        format!("Mindful Session extraction successful!\n\n{stats}\n{upload_message}").as_str(),
         MessageBoxIcon::Info,
     );
 